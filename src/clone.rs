@@ -0,0 +1,188 @@
+use indicatif::{MultiProgress, ProgressBar, ProgressStyle};
+use std::collections::VecDeque;
+use std::path::Path;
+use std::sync::{Arc, Mutex};
+use std::thread;
+
+#[derive(Debug)]
+pub enum Error {
+    Git(String),
+    IO(std::io::Error),
+}
+
+/// Clones every repository in `repositories` into its own `owner/name`
+/// subdirectory of `into`, fast-forwarding the default branch instead when a
+/// working tree already exists there. Runs up to `concurrency` repositories at
+/// a time, with a spinner per in-flight repository.
+pub fn clone_all(
+    repositories: Vec<String>,
+    into: &Path,
+    concurrency: usize,
+) -> Vec<(String, Result<(), Error>)> {
+    let queue = Arc::new(Mutex::new(VecDeque::from(repositories)));
+    let progress = Arc::new(MultiProgress::new());
+    let results = Arc::new(Mutex::new(Vec::new()));
+
+    let workers: Vec<_> = (0..concurrency.max(1))
+        .map(|_| {
+            let queue = queue.clone();
+            let progress = progress.clone();
+            let results = results.clone();
+            let into = into.to_path_buf();
+
+            thread::spawn(move || loop {
+                let repo = match queue.lock().unwrap().pop_front() {
+                    Some(repo) => repo,
+                    None => break,
+                };
+
+                let bar = progress.add(ProgressBar::new_spinner());
+                bar.set_style(
+                    ProgressStyle::default_spinner()
+                        .template("{spinner} {msg}")
+                        .expect("spinner template is valid"),
+                );
+                bar.enable_steady_tick(std::time::Duration::from_millis(100));
+
+                let destination = into.join(&repo);
+                let outcome = clone_or_fast_forward(&repo, &destination, &bar);
+
+                match &outcome {
+                    Ok(()) => bar.finish_with_message(format!("{}: up to date", repo)),
+                    Err(err) => bar.finish_with_message(format!("{}: failed ({:?})", repo, err)),
+                }
+
+                results.lock().unwrap().push((repo, outcome));
+            })
+        })
+        .collect();
+
+    for worker in workers {
+        let _ = worker.join();
+    }
+
+    Arc::try_unwrap(results)
+        .expect("all worker threads have been joined")
+        .into_inner()
+        .expect("results mutex is not poisoned")
+}
+
+fn clone_or_fast_forward(repo: &str, destination: &Path, bar: &ProgressBar) -> Result<(), Error> {
+    if destination.join(".git").exists() {
+        bar.set_message(format!("{}: fetching", repo));
+        fast_forward(destination)
+    } else {
+        bar.set_message(format!("{}: cloning", repo));
+
+        if let Some(parent) = destination.parent() {
+            std::fs::create_dir_all(parent).map_err(Error::IO)?;
+        }
+
+        let url = format!("https://github.com/{}.git", repo);
+        let mut prepare = gix::prepare_clone(url.as_str(), destination).map_err(git_err)?;
+        let (mut checkout, _outcome) = prepare
+            .fetch_then_checkout(gix::progress::Discard, &gix::interrupt::IS_INTERRUPTED)
+            .map_err(git_err)?;
+        checkout
+            .main_worktree(gix::progress::Discard, &gix::interrupt::IS_INTERRUPTED)
+            .map_err(git_err)?;
+
+        Ok(())
+    }
+}
+
+/// Fetches the default remote and fast-forwards the checked-out branch to its
+/// tip, then updates the working tree to match. Never rewrites local history:
+/// if the local branch isn't an ancestor of the fetched tip - because it has
+/// diverged, or has commits of its own - the update is reported as a failure
+/// rather than moving or rewinding the branch.
+fn fast_forward(destination: &Path) -> Result<(), Error> {
+    let repo = gix::open(destination).map_err(git_err)?;
+
+    let remote = repo
+        .find_default_remote(gix::remote::Direction::Fetch)
+        .transpose()
+        .map_err(git_err)?
+        .ok_or_else(|| Error::Git("repository has no configured remote".to_string()))?;
+
+    remote
+        .connect(gix::remote::Direction::Fetch)
+        .map_err(git_err)?
+        .prepare_fetch(gix::progress::Discard, Default::default())
+        .map_err(git_err)?
+        .receive(gix::progress::Discard, &gix::interrupt::IS_INTERRUPTED)
+        .map_err(git_err)?;
+
+    let mut head = repo.head_ref().map_err(git_err)?.ok_or_else(|| {
+        Error::Git("repository has no checked-out branch to fast-forward".to_string())
+    })?;
+    let local_head = head.id();
+
+    // A fetch always (re)writes `FETCH_HEAD`; unlike a remote-tracking ref
+    // such as `refs/remotes/origin/HEAD`, it doesn't depend on the remote
+    // helper having set one up.
+    let remote_head = repo
+        .find_reference("FETCH_HEAD")
+        .map_err(git_err)?
+        .into_fully_peeled_id()
+        .map_err(git_err)?;
+
+    if remote_head == local_head {
+        return Ok(());
+    }
+
+    if !is_ancestor(local_head, remote_head).map_err(git_err)? {
+        return Err(Error::Git(
+            "local branch has diverged from the remote - refusing to fast-forward".to_string(),
+        ));
+    }
+
+    head.set_target_id(remote_head, "gh-cli: fast-forward")
+        .map_err(git_err)?;
+
+    let tree_id = remote_head
+        .object()
+        .map_err(git_err)?
+        .peel_to_tree()
+        .map_err(git_err)?
+        .id;
+    let mut index = gix::index::File::from_state(
+        gix::index::State::from_tree(&tree_id, &repo.objects, Default::default()).map_err(git_err)?,
+        repo.index_path(),
+    );
+
+    gix::worktree::state::checkout(
+        &mut index,
+        destination,
+        repo.objects.clone(),
+        &gix::progress::Discard,
+        &gix::progress::Discard,
+        &gix::interrupt::IS_INTERRUPTED,
+        Default::default(),
+    )
+    .map_err(git_err)?;
+    index.write(Default::default()).map_err(git_err)?;
+
+    Ok(())
+}
+
+/// Whether `ancestor` is `descendant` itself or reachable by walking
+/// `descendant`'s parents - i.e. whether fast-forwarding `ancestor` to
+/// `descendant` would move it forward without discarding any commits.
+fn is_ancestor(ancestor: gix::Id<'_>, descendant: gix::Id<'_>) -> Result<bool, Box<dyn std::error::Error + Send + Sync>> {
+    if ancestor == descendant {
+        return Ok(true);
+    }
+
+    for commit in descendant.ancestors().all()? {
+        if commit?.id == ancestor {
+            return Ok(true);
+        }
+    }
+
+    Ok(false)
+}
+
+fn git_err(err: impl std::fmt::Display) -> Error {
+    Error::Git(err.to_string())
+}
@@ -0,0 +1,86 @@
+/// Scores how well `query` matches `candidate` as a subsequence: characters of
+/// `query` must appear in `candidate` in order, but not necessarily contiguous.
+/// Contiguous runs and matches at the start of a word are rewarded, gaps
+/// between matched characters are penalized. Returns `None` when `query` is
+/// not a subsequence of `candidate` at all (or ranks every candidate equally
+/// when `query` is empty).
+pub fn score(query: &str, candidate: &str) -> Option<i64> {
+    if query.is_empty() {
+        return Some(0);
+    }
+
+    let query: Vec<char> = query.to_lowercase().chars().collect();
+    let candidate: Vec<char> = candidate.to_lowercase().chars().collect();
+
+    let mut query_index = 0;
+    let mut previous_match: Option<usize> = None;
+    let mut total = 0i64;
+
+    for (candidate_index, &c) in candidate.iter().enumerate() {
+        if query_index == query.len() {
+            break;
+        }
+
+        if c != query[query_index] {
+            continue;
+        }
+
+        let mut bonus = 1;
+
+        let at_word_boundary = candidate_index == 0
+            || candidate
+                .get(candidate_index - 1)
+                .map_or(false, |previous| !previous.is_alphanumeric());
+        if at_word_boundary {
+            bonus += 8;
+        }
+
+        match previous_match {
+            Some(previous) if previous + 1 == candidate_index => bonus += 4,
+            Some(previous) => bonus -= (candidate_index - previous) as i64,
+            None => {}
+        }
+
+        total += bonus;
+        previous_match = Some(candidate_index);
+        query_index += 1;
+    }
+
+    if query_index == query.len() {
+        Some(total)
+    } else {
+        None
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::score;
+
+    #[test]
+    fn empty_query_matches_everything_with_equal_score() {
+        assert_eq!(score("", "anything"), Some(0));
+        assert_eq!(score("", ""), Some(0));
+    }
+
+    #[test]
+    fn characters_out_of_order_do_not_match() {
+        assert_eq!(score("ba", "ab"), None);
+    }
+
+    #[test]
+    fn prefers_contiguous_matches() {
+        // `z` filler keeps every match off a word boundary, so the only
+        // difference between the two candidates is how spread out the match is.
+        let contiguous = score("fix", "zfix").unwrap();
+        let scattered = score("fix", "zfzizx").unwrap();
+        assert!(contiguous > scattered);
+    }
+
+    #[test]
+    fn prefers_matches_at_a_word_boundary() {
+        let at_boundary = score("fix", "fix-bug").unwrap();
+        let mid_word = score("fix", "prefix-bug").unwrap();
+        assert!(at_boundary > mid_word);
+    }
+}
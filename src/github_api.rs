@@ -0,0 +1,27 @@
+mod auth;
+mod backoff;
+mod ci_status;
+mod client;
+mod milestones;
+
+pub use auth::{AppCredentials, Credentials, TokenManager};
+pub use ci_status::Gate;
+pub use client::{Client, CreateMilestone, MilestonesClient, RepoClient};
+
+#[derive(Debug)]
+pub enum Error {
+    Reqwest(reqwest::Error),
+    /// GitHub responded with a non-2xx status. The response body is not
+    /// read, so any error payload GitHub sent is not included.
+    UnexpectedStatus(reqwest::StatusCode),
+    Jwt(jsonwebtoken::errors::Error),
+    Timer(tokio::timer::Error),
+    IO(std::io::Error),
+}
+
+#[derive(serde::Deserialize, Clone, Debug)]
+pub struct Milestone {
+    pub number: u64,
+    pub title: String,
+    pub url: String,
+}
@@ -0,0 +1,139 @@
+use super::Error;
+use crate::secret::Secret;
+use chrono::{DateTime, Duration, Utc};
+use futures::future::Future;
+use reqwest::r#async::{Client as HttpClient, RequestBuilder};
+use std::path::PathBuf;
+use std::sync::{Arc, Mutex};
+
+/// Credentials for a GitHub App installation, as configured in `settings.toml`.
+#[derive(Clone, serde::Deserialize)]
+pub struct AppCredentials {
+    pub app_id: u64,
+    pub private_key: PathBuf,
+    pub installation_id: u64,
+}
+
+struct CachedToken {
+    token: String,
+    expires_at: DateTime<Utc>,
+}
+
+/// Mints and caches short-lived installation tokens for a GitHub App, re-minting
+/// transparently whenever the cached token is close to expiry.
+#[derive(Clone)]
+pub struct TokenManager {
+    http: HttpClient,
+    base_url: String,
+    credentials: AppCredentials,
+    cached: Arc<Mutex<Option<CachedToken>>>,
+}
+
+#[derive(serde::Serialize)]
+struct Claims {
+    iat: i64,
+    exp: i64,
+    iss: u64,
+}
+
+#[derive(serde::Deserialize)]
+struct AccessTokenResponse {
+    token: String,
+    expires_at: DateTime<Utc>,
+}
+
+const EXPIRY_SAFETY_MARGIN: i64 = 60;
+
+impl TokenManager {
+    pub fn new(http: HttpClient, base_url: String, credentials: AppCredentials) -> Self {
+        TokenManager {
+            http,
+            base_url,
+            credentials,
+            cached: Arc::new(Mutex::new(None)),
+        }
+    }
+
+    /// Returns a currently-valid installation token, minting a new one if the
+    /// cached token is missing or within `EXPIRY_SAFETY_MARGIN` seconds of expiry.
+    pub fn token(&self) -> Box<dyn Future<Item = String, Error = Error> + Send> {
+        if let Some(cached) = self.cached.lock().unwrap().as_ref() {
+            if cached.expires_at - Utc::now() > Duration::seconds(EXPIRY_SAFETY_MARGIN) {
+                return Box::new(futures::future::ok(cached.token.clone()));
+            }
+        }
+
+        let jwt = match self.sign_jwt() {
+            Ok(jwt) => jwt,
+            Err(err) => return Box::new(futures::future::err(err)),
+        };
+
+        let cached = self.cached.clone();
+        let url = format!(
+            "{}/app/installations/{}/access_tokens",
+            self.base_url, self.credentials.installation_id
+        );
+
+        Box::new(
+            self.http
+                .post(&url)
+                .header("Accept", "application/vnd.github.v3+json")
+                .bearer_auth(jwt)
+                .send()
+                .map_err(Error::Reqwest)
+                .and_then(|mut response| {
+                    response
+                        .json::<AccessTokenResponse>()
+                        .map_err(Error::Reqwest)
+                })
+                .map(move |response| {
+                    let token = response.token.clone();
+                    *cached.lock().unwrap() = Some(CachedToken {
+                        token: response.token,
+                        expires_at: response.expires_at,
+                    });
+                    token
+                }),
+        )
+    }
+
+    fn sign_jwt(&self) -> Result<String, Error> {
+        let now = Utc::now().timestamp();
+        let claims = Claims {
+            iat: now - EXPIRY_SAFETY_MARGIN,
+            exp: now + 600,
+            iss: self.credentials.app_id,
+        };
+
+        let private_key = std::fs::read(&self.credentials.private_key).map_err(Error::IO)?;
+        let encoding_key =
+            jsonwebtoken::EncodingKey::from_rsa_pem(&private_key).map_err(Error::Jwt)?;
+        let header = jsonwebtoken::Header::new(jsonwebtoken::Algorithm::RS256);
+
+        jsonwebtoken::encode(&header, &claims, &encoding_key).map_err(Error::Jwt)
+    }
+}
+
+/// The credentials used to authenticate outgoing requests: either a static
+/// basic-auth pair, or a GitHub App installation whose token is refreshed on demand.
+#[derive(Clone)]
+pub enum Credentials {
+    Basic { username: String, token: Secret },
+    App(TokenManager),
+}
+
+impl Credentials {
+    pub fn authorize(
+        &self,
+        builder: RequestBuilder,
+    ) -> Box<dyn Future<Item = RequestBuilder, Error = Error> + Send> {
+        match self {
+            Credentials::Basic { username, token } => Box::new(futures::future::ok(
+                builder.basic_auth(username.clone(), Some(token.expose().to_string())),
+            )),
+            Credentials::App(manager) => {
+                Box::new(manager.token().map(move |token| builder.bearer_auth(token)))
+            }
+        }
+    }
+}
@@ -0,0 +1,167 @@
+use super::{Credentials, Error};
+use futures::future::{self, loop_fn, Future, Loop};
+use rand::Rng;
+use reqwest::r#async::{Client as HttpClient, RequestBuilder, Response};
+use std::time::{Duration, Instant};
+use tokio::timer::Delay;
+
+/// Sends a request built by `build_request`, retrying in place whenever the
+/// response signals that we've hit GitHub's primary or secondary rate limit.
+/// `build_request` is called again for every retry, so it must be able to
+/// produce an equivalent request each time.
+pub fn send_with_backoff<F>(
+    credentials: Credentials,
+    build_request: F,
+) -> Box<dyn Future<Item = Response, Error = Error> + Send>
+where
+    F: Fn() -> RequestBuilder + Send + 'static,
+{
+    Box::new(loop_fn(
+        build_request,
+        move |build_request| -> Box<dyn Future<Item = Loop<Response, F>, Error = Error> + Send> {
+            let credentials = credentials.clone();
+            let builder = build_request();
+
+            Box::new(
+                credentials
+                    .authorize(builder)
+                    .and_then(|request| request.send().map_err(Error::Reqwest))
+                    .and_then(move |response| match rate_limit_delay(&response) {
+                        Some(delay) => Box::new(
+                            Delay::new(Instant::now() + delay)
+                                .map_err(Error::Timer)
+                                .map(move |()| Loop::Continue(build_request)),
+                        )
+                            as Box<dyn Future<Item = Loop<Response, F>, Error = Error> + Send>,
+                        None => Box::new(future::ok(Loop::Break(response))),
+                    }),
+            )
+        },
+    ))
+}
+
+/// If `response` indicates a rate limit has been exhausted, returns how long to
+/// wait before retrying: honoring `Retry-After` for secondary limits (with a
+/// little jitter so many repositories don't all wake up at once), or sleeping
+/// until `X-RateLimit-Reset` when `X-RateLimit-Remaining` has hit zero.
+pub fn rate_limit_delay(response: &Response) -> Option<Duration> {
+    let status = response.status();
+    if status != reqwest::StatusCode::FORBIDDEN && status != reqwest::StatusCode::TOO_MANY_REQUESTS
+    {
+        return None;
+    }
+
+    if let Some(retry_after) = header_u64(response, "retry-after") {
+        let jitter = rand::thread_rng().gen_range(0, 5);
+        return Some(Duration::from_secs(retry_after + jitter));
+    }
+
+    if header_u64(response, "x-ratelimit-remaining") == Some(0) {
+        let reset_at = header_u64(response, "x-ratelimit-reset")?;
+        let now = chrono::Utc::now().timestamp() as u64;
+        return Some(Duration::from_secs(reset_at.saturating_sub(now) + 1));
+    }
+
+    None
+}
+
+pub fn header_u64(response: &Response, name: &str) -> Option<u64> {
+    response.headers().get(name)?.to_str().ok()?.parse().ok()
+}
+
+/// Fails with `Error::UnexpectedStatus` if `response`'s status isn't a
+/// success, so a GitHub error body (which won't deserialize as the expected
+/// type) is reported instead of either a confusing JSON error or, for
+/// responses we otherwise ignore, being silently treated as a success.
+pub fn ensure_success(response: Response) -> Result<Response, Error> {
+    if response.status().is_success() {
+        Ok(response)
+    } else {
+        Err(Error::UnexpectedStatus(response.status()))
+    }
+}
+
+/// GETs `url` and deserializes the JSON body as `T`.
+pub fn get_json<T>(
+    client: HttpClient,
+    credentials: Credentials,
+    url: String,
+) -> Box<dyn Future<Item = T, Error = Error> + Send>
+where
+    T: serde::de::DeserializeOwned + Send + 'static,
+{
+    Box::new(
+        send_with_backoff(credentials, move || {
+            client
+                .get(&url)
+                .header("Accept", "application/vnd.github.v3+json")
+        })
+        .and_then(ensure_success)
+        .and_then(|mut response| response.json::<T>().map_err(Error::Reqwest)),
+    )
+}
+
+struct PaginationState<T> {
+    next_url: Option<String>,
+    items: Vec<T>,
+}
+
+/// GETs `first_url` and every subsequent `rel="next"` page, concatenating the
+/// JSON arrays into a single `Vec<T>`.
+pub fn paginate<T>(
+    client: HttpClient,
+    credentials: Credentials,
+    first_url: String,
+) -> Box<dyn Future<Item = Vec<T>, Error = Error> + Send>
+where
+    T: serde::de::DeserializeOwned + Send + 'static,
+{
+    Box::new(loop_fn(
+        PaginationState {
+            next_url: Some(first_url),
+            items: Vec::new(),
+        },
+        move |state| -> Box<dyn Future<Item = Loop<Vec<T>, PaginationState<T>>, Error = Error> + Send> {
+            let url = match state.next_url {
+                Some(url) => url,
+                None => return Box::new(future::ok(Loop::Break(state.items))),
+            };
+
+            let mut items = state.items;
+            let client = client.clone();
+
+            Box::new(
+                send_with_backoff(credentials.clone(), move || {
+                    client
+                        .get(&url)
+                        .header("Accept", "application/vnd.github.v3+json")
+                })
+                .and_then(ensure_success)
+                .and_then(move |mut response| {
+                    let next_url = next_page_url(&response);
+
+                    response.json::<Vec<T>>().map_err(Error::Reqwest).map(move |mut page| {
+                        items.append(&mut page);
+                        Loop::Continue(PaginationState { next_url, items })
+                    })
+                }),
+            )
+        },
+    ))
+}
+
+fn next_page_url(response: &Response) -> Option<String> {
+    let link_header = response.headers().get(reqwest::header::LINK)?.to_str().ok()?;
+
+    link_header.split(',').find_map(|link| {
+        let mut segments = link.split(';');
+        let url = segments.next()?.trim();
+        let is_next = segments.any(|segment| segment.trim() == r#"rel="next""#);
+
+        if is_next {
+            Some(url.trim_start_matches('<').trim_end_matches('>').to_string())
+        } else {
+            None
+        }
+    })
+}
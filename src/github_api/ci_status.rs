@@ -0,0 +1,187 @@
+use super::backoff::{get_json, paginate};
+use super::{Credentials, Error};
+use futures::future::{self, Future};
+use reqwest::r#async::Client as HttpClient;
+
+#[derive(serde::Deserialize)]
+struct Issue {
+    number: u64,
+    pull_request: Option<serde_json::Value>,
+}
+
+#[derive(serde::Deserialize)]
+struct PullRequest {
+    head: PullRequestHead,
+}
+
+#[derive(serde::Deserialize)]
+struct PullRequestHead {
+    sha: String,
+}
+
+#[derive(serde::Deserialize)]
+struct CombinedStatus {
+    state: String,
+    total_count: u64,
+}
+
+#[derive(serde::Deserialize)]
+struct CheckRunsResponse {
+    check_runs: Vec<CheckRun>,
+}
+
+#[derive(serde::Deserialize)]
+struct CheckRun {
+    status: String,
+    conclusion: Option<String>,
+}
+
+/// Whether the work attached to a milestone is safe to close.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Gate {
+    /// Every attached pull request has a successful combined status and
+    /// check-runs (or none are attached at all).
+    Closeable,
+    /// At least one attached pull request's status or check-run is still
+    /// pending, or a check-run completed with a conclusion that is neither a
+    /// success nor a failure (such as `action_required`).
+    Pending,
+    /// At least one attached pull request's status or check-run has failed.
+    Blocked,
+}
+
+/// The more conservative of two gates, so a single failing or pending signal
+/// (from either the legacy commit-status API or GitHub Actions check-runs)
+/// overrides an otherwise-green one.
+fn worst_of(a: Gate, b: Gate) -> Gate {
+    match (a, b) {
+        (Gate::Blocked, _) | (_, Gate::Blocked) => Gate::Blocked,
+        (Gate::Pending, _) | (_, Gate::Pending) => Gate::Pending,
+        (Gate::Closeable, Gate::Closeable) => Gate::Closeable,
+    }
+}
+
+/// Checks whether milestone `number` in `repo` is safe to close: every open
+/// pull request tied to it must have a successful combined commit status and
+/// check-run conclusion for its head commit.
+pub fn check_milestone(
+    client: HttpClient,
+    credentials: Credentials,
+    base_url: String,
+    repo: String,
+    number: u64,
+) -> Box<dyn Future<Item = Gate, Error = Error> + Send> {
+    let issues_url = format!(
+        "{}/repos/{}/issues?milestone={}&state=open",
+        base_url, repo, number
+    );
+
+    Box::new(
+        paginate::<Issue>(client.clone(), credentials.clone(), issues_url).and_then(
+            move |issues| {
+                let pull_request_numbers: Vec<u64> = issues
+                    .into_iter()
+                    .filter(|issue| issue.pull_request.is_some())
+                    .map(|issue| issue.number)
+                    .collect();
+
+                if pull_request_numbers.is_empty() {
+                    return Box::new(future::ok(Gate::Closeable))
+                        as Box<dyn Future<Item = Gate, Error = Error> + Send>;
+                }
+
+                let gates = pull_request_numbers.into_iter().map(move |pr_number| {
+                    gate_for_pull_request(
+                        client.clone(),
+                        credentials.clone(),
+                        base_url.clone(),
+                        repo.clone(),
+                        pr_number,
+                    )
+                });
+
+                Box::new(
+                    future::join_all(gates)
+                        .map(|gates| gates.into_iter().fold(Gate::Closeable, worst_of)),
+                )
+            },
+        ),
+    )
+}
+
+/// Resolves the gate for a single pull request by combining its legacy
+/// combined commit status with its GitHub Actions check-runs - a repo whose
+/// CI runs entirely through Actions has no commit statuses at all, so relying
+/// on either signal alone misses half of what "green" means in practice.
+fn gate_for_pull_request(
+    client: HttpClient,
+    credentials: Credentials,
+    base_url: String,
+    repo: String,
+    pr_number: u64,
+) -> Box<dyn Future<Item = Gate, Error = Error> + Send> {
+    let pull_url = format!("{}/repos/{}/pulls/{}", base_url, repo, pr_number);
+
+    Box::new(
+        get_json::<PullRequest>(client.clone(), credentials.clone(), pull_url).and_then(
+            move |pull_request| {
+                let sha = pull_request.head.sha;
+                let status_url = format!("{}/repos/{}/commits/{}/status", base_url, repo, sha);
+                let check_runs_url =
+                    format!("{}/repos/{}/commits/{}/check-runs", base_url, repo, sha);
+
+                let combined_status =
+                    get_json::<CombinedStatus>(client.clone(), credentials.clone(), status_url)
+                        .map(|combined| gate_of_combined_status(&combined));
+                let check_runs = get_json::<CheckRunsResponse>(client, credentials, check_runs_url)
+                    .map(|response| gate_of_check_runs(&response.check_runs));
+
+                combined_status
+                    .join(check_runs)
+                    .map(|(status_gate, check_runs_gate)| worst_of(status_gate, check_runs_gate))
+            },
+        ),
+    )
+}
+
+/// A commit with no legacy statuses at all (the common case for a repo whose
+/// CI runs entirely through Actions) still reports `state: "pending"`, not
+/// "success" - so an absent combined status must be treated as having
+/// nothing to say, not as CI being stuck.
+fn gate_of_combined_status(combined: &CombinedStatus) -> Gate {
+    if combined.total_count == 0 {
+        Gate::Closeable
+    } else if is_failure(&combined.state) {
+        Gate::Blocked
+    } else if combined.state == "pending" {
+        Gate::Pending
+    } else {
+        Gate::Closeable
+    }
+}
+
+fn gate_of_check_runs(check_runs: &[CheckRun]) -> Gate {
+    check_runs
+        .iter()
+        .map(gate_of_check_run)
+        .fold(Gate::Closeable, worst_of)
+}
+
+fn gate_of_check_run(check_run: &CheckRun) -> Gate {
+    if check_run.status != "completed" {
+        return Gate::Pending;
+    }
+
+    match check_run.conclusion.as_deref() {
+        Some("failure") | Some("timed_out") | Some("cancelled") => Gate::Blocked,
+        Some("success") | Some("neutral") | Some("skipped") | None => Gate::Closeable,
+        // `action_required`, `stale`, and any future conclusion GitHub adds
+        // aren't outright failures, but they aren't green either - treat
+        // them as needing attention rather than as a hard block.
+        _ => Gate::Pending,
+    }
+}
+
+fn is_failure(state: &str) -> bool {
+    state == "failure" || state == "error"
+}
@@ -0,0 +1,116 @@
+use super::{ci_status, milestones, Credentials, Error, Gate, Milestone};
+use chrono::{DateTime, Utc};
+use futures::future::Future;
+use reqwest::r#async::Client as HttpClient;
+
+const DEFAULT_BASE_URL: &str = "https://api.github.com";
+
+/// A typed GitHub client: owns the HTTP client, credentials and base URL (so
+/// GitHub Enterprise hosts can be targeted), and exposes operations through a
+/// fluent, per-repository service layer.
+#[derive(Clone)]
+pub struct Client {
+    http: HttpClient,
+    credentials: Credentials,
+    base_url: String,
+}
+
+impl Client {
+    pub fn new(credentials: Credentials) -> Self {
+        Client::with_base_url(credentials, DEFAULT_BASE_URL)
+    }
+
+    pub fn with_base_url(credentials: Credentials, base_url: impl Into<String>) -> Self {
+        Client {
+            http: HttpClient::new(),
+            credentials,
+            base_url: base_url.into(),
+        }
+    }
+
+    pub fn repo(&self, owner: &str, name: &str) -> RepoClient {
+        RepoClient {
+            client: self.clone(),
+            repo: format!("{}/{}", owner, name),
+        }
+    }
+}
+
+/// Scopes further operations to a single repository.
+#[derive(Clone)]
+pub struct RepoClient {
+    client: Client,
+    repo: String,
+}
+
+impl RepoClient {
+    pub fn milestones(&self) -> MilestonesClient {
+        MilestonesClient {
+            client: self.client.clone(),
+            repo: self.repo.clone(),
+        }
+    }
+}
+
+/// Milestone operations scoped to a single repository.
+#[derive(Clone)]
+pub struct MilestonesClient {
+    client: Client,
+    repo: String,
+}
+
+impl MilestonesClient {
+    pub fn list(&self) -> Box<dyn Future<Item = Vec<Milestone>, Error = Error> + Send> {
+        milestones::list_all(
+            self.client.http.clone(),
+            self.client.credentials.clone(),
+            self.client.base_url.clone(),
+            self.repo.clone(),
+        )
+    }
+
+    pub fn close(&self, number: u64) -> Box<dyn Future<Item = (), Error = Error> + Send> {
+        milestones::close(
+            self.client.http.clone(),
+            self.client.credentials.clone(),
+            self.client.base_url.clone(),
+            self.repo.clone(),
+            number,
+        )
+    }
+
+    pub fn create(
+        &self,
+        new_milestone: CreateMilestone,
+    ) -> Box<dyn Future<Item = Milestone, Error = Error> + Send> {
+        milestones::create(
+            self.client.http.clone(),
+            self.client.credentials.clone(),
+            self.client.base_url.clone(),
+            self.repo.clone(),
+            new_milestone,
+        )
+    }
+
+    /// Checks whether every open pull request attached to milestone `number`
+    /// has green CI, combining its commit statuses and its check-runs.
+    pub fn check_gate(&self, number: u64) -> Box<dyn Future<Item = Gate, Error = Error> + Send> {
+        ci_status::check_milestone(
+            self.client.http.clone(),
+            self.client.credentials.clone(),
+            self.client.base_url.clone(),
+            self.repo.clone(),
+            number,
+        )
+    }
+}
+
+/// Request body for creating a milestone.
+#[derive(Default, serde::Serialize)]
+pub struct CreateMilestone {
+    pub title: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub description: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub due_on: Option<DateTime<Utc>>,
+}
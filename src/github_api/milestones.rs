@@ -0,0 +1,65 @@
+use super::backoff::{ensure_success, paginate, send_with_backoff};
+use super::{Credentials, Error, Milestone};
+use futures::future::Future;
+use reqwest::r#async::Client as HttpClient;
+
+/// Fetches every open milestone for `repo`, following `rel="next"` `Link` headers
+/// until GitHub stops returning a next page.
+pub fn list_all(
+    client: HttpClient,
+    credentials: Credentials,
+    base_url: String,
+    repo: String,
+) -> Box<dyn Future<Item = Vec<Milestone>, Error = Error> + Send> {
+    let first_page = format!("{}/repos/{}/milestones", base_url, repo);
+    paginate(client, credentials, first_page)
+}
+
+/// Closes the milestone identified by `number` in `repo`.
+pub fn close(
+    client: HttpClient,
+    credentials: Credentials,
+    base_url: String,
+    repo: String,
+    number: u64,
+) -> Box<dyn Future<Item = (), Error = Error> + Send> {
+    let url = format!("{}/repos/{}/milestones/{}", base_url, repo, number);
+
+    Box::new(
+        send_with_backoff(credentials, move || {
+            client
+                .patch(&url)
+                .header("Accept", "application/vnd.github.v3+json")
+                .json(&serde_json::json!({ "state": "closed" }))
+        })
+        .and_then(ensure_success)
+        .map(|_response| ()),
+    )
+}
+
+/// Creates a new milestone in `repo` from the given request body.
+pub fn create<T>(
+    client: HttpClient,
+    credentials: Credentials,
+    base_url: String,
+    repo: String,
+    body: T,
+) -> Box<dyn Future<Item = Milestone, Error = Error> + Send>
+where
+    T: serde::Serialize + Send + 'static,
+{
+    let url = format!("{}/repos/{}/milestones", base_url, repo);
+    let body = std::sync::Arc::new(body);
+
+    Box::new(
+        send_with_backoff(credentials, move || {
+            let body = body.clone();
+            client
+                .post(&url)
+                .header("Accept", "application/vnd.github.v3+json")
+                .json(&*body)
+        })
+        .and_then(ensure_success)
+        .and_then(|mut response| response.json::<Milestone>().map_err(Error::Reqwest)),
+    )
+}
@@ -0,0 +1,135 @@
+use crate::fuzzy;
+use console::{Key, Term};
+use std::collections::HashSet;
+use std::io;
+
+/// A live, fuzzy-filtered multi-select prompt: the user types to narrow the
+/// list, toggles entries with space, and confirms the whole selection with
+/// Enter. Re-ranks on every keystroke using [`fuzzy::score`].
+pub struct FuzzyMultiSelect<'a> {
+    items: &'a [String],
+}
+
+impl<'a> FuzzyMultiSelect<'a> {
+    pub fn new(items: &'a [String]) -> Self {
+        FuzzyMultiSelect { items }
+    }
+
+    /// Runs the prompt on stderr, returning the indices (into the
+    /// original `items` slice) that were selected when the user pressed Enter.
+    /// Returns an empty selection if the user cancels with Escape.
+    pub fn interact(&self) -> io::Result<Vec<usize>> {
+        let term = Term::stderr();
+        let mut query = String::new();
+        let mut selected: HashSet<usize> = HashSet::new();
+        let mut cursor = 0usize;
+        let mut lines_drawn = 0u16;
+
+        loop {
+            let ranked = self.rank(&query);
+            cursor = cursor.min(ranked.len().saturating_sub(1));
+
+            term.clear_last_lines(lines_drawn as usize)?;
+            lines_drawn = self.render(&term, &query, &ranked, &selected, cursor)?;
+
+            match term.read_key()? {
+                Key::Enter => {
+                    term.clear_last_lines(lines_drawn as usize)?;
+                    let mut indices: Vec<usize> = selected.into_iter().collect();
+                    indices.sort_unstable();
+                    return Ok(indices);
+                }
+                Key::Escape => {
+                    term.clear_last_lines(lines_drawn as usize)?;
+                    return Ok(Vec::new());
+                }
+                Key::ArrowUp => cursor = cursor.saturating_sub(1),
+                Key::ArrowDown => cursor = (cursor + 1).min(ranked.len().saturating_sub(1)),
+                Key::Char(' ') => {
+                    if let Some((index, _, _)) = ranked.get(cursor) {
+                        if !selected.remove(index) {
+                            selected.insert(*index);
+                        }
+                    }
+                }
+                Key::Backspace => {
+                    query.pop();
+                    cursor = 0;
+                }
+                Key::Char(c) => {
+                    query.push(c);
+                    cursor = 0;
+                }
+                _ => {}
+            }
+        }
+    }
+
+    fn rank(&self, query: &str) -> Vec<(usize, &'a str, i64)> {
+        let mut ranked: Vec<(usize, &str, i64)> = self
+            .items
+            .iter()
+            .enumerate()
+            .filter_map(|(index, item)| {
+                fuzzy::score(query, item).map(|score| (index, item.as_str(), score))
+            })
+            .collect();
+
+        ranked.sort_by(|a, b| b.2.cmp(&a.2));
+        ranked
+    }
+
+    /// Renders the filter line followed by a scrolled window of `ranked`,
+    /// bounded to the terminal height so a large result set can't overflow it
+    /// (and leave `clear_last_lines` clearing too few rows on the next
+    /// redraw). Returns the number of lines written, for the caller to pass
+    /// back to `clear_last_lines`.
+    fn render(
+        &self,
+        term: &Term,
+        query: &str,
+        ranked: &[(usize, &str, i64)],
+        selected: &HashSet<usize>,
+        cursor: usize,
+    ) -> io::Result<u16> {
+        term.write_line(&format!("Filter: {}", query))?;
+        let mut lines_drawn = 1u16;
+
+        let (rows, _cols) = term.size();
+        let max_visible = (rows as usize).saturating_sub(3).max(1);
+        let window = scroll_window(ranked.len(), cursor, max_visible);
+
+        if window.start > 0 {
+            term.write_line(&format!("  ... {} more above", window.start))?;
+            lines_drawn += 1;
+        }
+
+        for (row, (index, title, _)) in ranked[window.clone()].iter().enumerate() {
+            let pointer = if window.start + row == cursor { ">" } else { " " };
+            let checkbox = if selected.contains(index) { "[x]" } else { "[ ]" };
+            term.write_line(&format!("{} {} {}", pointer, checkbox, title))?;
+            lines_drawn += 1;
+        }
+
+        if window.end < ranked.len() {
+            term.write_line(&format!("  ... {} more below", ranked.len() - window.end))?;
+            lines_drawn += 1;
+        }
+
+        Ok(lines_drawn)
+    }
+}
+
+/// Picks a contiguous sub-range of at most `max_visible` items out of `total`
+/// that keeps `cursor` in view, scrolling the window as the cursor moves.
+fn scroll_window(total: usize, cursor: usize, max_visible: usize) -> std::ops::Range<usize> {
+    if total <= max_visible {
+        return 0..total;
+    }
+
+    let start = cursor
+        .saturating_sub(max_visible / 2)
+        .min(total - max_visible);
+
+    start..start + max_visible
+}
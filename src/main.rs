@@ -1,12 +1,19 @@
+mod clone;
+mod fuzzy;
 mod github_api;
+mod interactive;
+mod secret;
 
-use crate::github_api::Milestone;
+use crate::github_api::{Client, Credentials, Milestone, TokenManager};
+use crate::interactive::FuzzyMultiSelect;
 use std::collections::hash_map::Entry;
 use std::collections::HashMap;
 use structopt::StructOpt;
 use tokio::prelude::*;
 
 mod settings {
+    use crate::github_api::AppCredentials;
+
     #[derive(serde::Deserialize, Default)]
     pub struct Root {
         pub github: GitHub,
@@ -18,12 +25,22 @@ mod settings {
         pub repositories: Vec<String>,
         #[serde(default)]
         pub auth: Option<Authentication>,
+        /// The API base URL, for GitHub Enterprise hosts. Defaults to github.com.
+        #[serde(default)]
+        pub base_url: Option<String>,
     }
 
     #[derive(serde::Deserialize)]
-    pub struct Authentication {
-        pub username: String,
-        pub token: String,
+    #[serde(untagged)]
+    pub enum Authentication {
+        Basic {
+            username: String,
+            /// A literal token, `env:NAME` to read it from an environment
+            /// variable, or `keyring:SERVICE` to read it from the OS
+            /// credential store.
+            token: String,
+        },
+        GitHubApp(AppCredentials),
     }
 }
 
@@ -32,8 +49,28 @@ enum Commands {
     #[structopt(name = "close-milestone")]
     /// Close the given milestone for all configured repositories
     CloseMilestone {
-        /// A regular expression matching against the milestone name
-        pattern: regex::Regex,
+        /// A regular expression matching against the milestone name. When
+        /// omitted, or when `--interactive` is passed, an interactive
+        /// fuzzy-filtered multi-select is shown instead.
+        pattern: Option<regex::Regex>,
+        /// Show the interactive fuzzy-filtered multi-select even if a
+        /// pattern was given
+        #[structopt(long)]
+        interactive: bool,
+        /// Refuse to close a milestone if any of its open pull requests have
+        /// a pending or failing commit status or check-run
+        #[structopt(long)]
+        require_green: bool,
+    },
+    #[structopt(name = "clone")]
+    /// Clone (or fast-forward update) every configured repository into a local directory
+    Clone {
+        /// Directory to clone repositories into
+        #[structopt(long, parse(from_os_str), default_value = ".")]
+        into: std::path::PathBuf,
+        /// Maximum number of repositories to clone concurrently
+        #[structopt(long, default_value = "4")]
+        concurrency: usize,
     },
 }
 
@@ -41,7 +78,8 @@ enum Commands {
 enum Error {
     NoConfigDir,
     InvalidConfigFile(config::ConfigError),
-    Reqwest(reqwest::Error),
+    GithubApi(github_api::Error),
+    Secret(secret::Error),
     AuthRequired,
     IO(std::io::Error),
 }
@@ -51,6 +89,57 @@ struct RepositoryMilestones {
     milestones: Vec<Milestone>,
 }
 
+/// Splits a configured `owner/name` repository string into its two parts.
+fn split_repo(repo: &str) -> (&str, &str) {
+    let mut parts = repo.splitn(2, '/');
+    let owner = parts.next().unwrap_or(repo);
+    let name = parts.next().unwrap_or("");
+    (owner, name)
+}
+
+enum CloseOutcome {
+    Closed,
+    SkippedPending,
+    Blocked,
+    Failed(github_api::Error),
+}
+
+/// Closes `number` via `milestones`, first checking its CI gate when
+/// `require_green` is set: a pending or failing status skips or blocks the
+/// close instead of going ahead.
+fn close_with_gate(
+    milestones: github_api::MilestonesClient,
+    number: u64,
+    require_green: bool,
+) -> Box<dyn Future<Item = CloseOutcome, Error = Error> + Send> {
+    if !require_green {
+        return Box::new(milestones.close(number).then(|result| Ok(outcome_of(result))));
+    }
+
+    let close_milestones = milestones.clone();
+
+    Box::new(milestones.check_gate(number).then(move |gate| {
+        let outcome: Box<dyn Future<Item = CloseOutcome, Error = Error> + Send> = match gate {
+            Ok(github_api::Gate::Closeable) => Box::new(
+                close_milestones
+                    .close(number)
+                    .then(|result| Ok(outcome_of(result))),
+            ),
+            Ok(github_api::Gate::Pending) => Box::new(future::ok(CloseOutcome::SkippedPending)),
+            Ok(github_api::Gate::Blocked) => Box::new(future::ok(CloseOutcome::Blocked)),
+            Err(err) => Box::new(future::ok(CloseOutcome::Failed(err))),
+        };
+        outcome
+    }))
+}
+
+fn outcome_of(result: Result<(), github_api::Error>) -> CloseOutcome {
+    match result {
+        Ok(()) => CloseOutcome::Closed,
+        Err(err) => CloseOutcome::Failed(err),
+    }
+}
+
 fn main() -> Result<(), Error> {
     let project_dir =
         directories::ProjectDirs::from("tech", "coblox", "GH CLI").ok_or(Error::NoConfigDir)?;
@@ -83,49 +172,63 @@ fn main() -> Result<(), Error> {
     let command = Commands::from_args();
 
     match command {
-        Commands::CloseMilestone { pattern } => {
+        Commands::CloseMilestone {
+            pattern,
+            interactive,
+            require_green,
+        } => {
             let settings::Root {
-                github: settings::GitHub { repositories, auth },
+                github:
+                    settings::GitHub {
+                        repositories,
+                        auth,
+                        base_url,
+                    },
             } = settings;
 
-            let settings::Authentication { username, token } = auth.ok_or(Error::AuthRequired)?;
-            let client = reqwest::r#async::Client::new();
+            let base_url =
+                base_url.unwrap_or_else(|| "https://api.github.com".to_string());
+
+            let credentials = match auth.ok_or(Error::AuthRequired)? {
+                settings::Authentication::Basic { username, token } => {
+                    let token = secret::Secret::resolve(&token, &username).map_err(Error::Secret)?;
+                    Credentials::Basic { username, token }
+                }
+                settings::Authentication::GitHubApp(app_credentials) => Credentials::App(
+                    TokenManager::new(
+                        reqwest::r#async::Client::new(),
+                        base_url.clone(),
+                        app_credentials,
+                    ),
+                ),
+            };
+            let client = Client::with_base_url(credentials, base_url);
+            let interactive = interactive || pattern.is_none();
 
-            let matching_milestones = {
-                let username = username.clone();
-                let token = token.clone();
+            let milestones_by_title: HashMap<String, Vec<(u64, String)>> = {
                 let client = client.clone();
 
                 let repository_milestones: Vec<RepositoryMilestones> = runtime
                     .block_on(future::join_all(repositories.into_iter().map(
                         move |repo| {
+                            let repo_clone = repo.clone();
+                            let (owner, name) = split_repo(&repo);
+
                             client
-                                .clone()
-                                .get(&format!("https://api.github.com/repos/{}/milestones", repo))
-                                .header("Accept", "application/vnd.github.v3+json")
-                                .basic_auth(username.clone(), Some(token.clone()))
-                                .send()
-                                .and_then(|mut response| {
-                                    let repo_clone = repo.clone();
-
-                                    response
-                                        .json::<Vec<github_api::Milestone>>()
-                                        .or_else(move |_| {
-                                            eprintln!(
-                                                "Request to {} failed with statuscode {}",
-                                                repo_clone,
-                                                response.status().as_u16()
-                                            );
-                                            Ok(Vec::new())
-                                        })
-                                        .map(move |milestones| RepositoryMilestones {
-                                            repository: repo.clone(),
-                                            milestones,
-                                        })
+                                .repo(owner, name)
+                                .milestones()
+                                .list()
+                                .map_err(Error::GithubApi)
+                                .or_else(move |err| {
+                                    eprintln!("Request to {} failed: {:?}", repo_clone, err);
+                                    Ok(Vec::new())
+                                })
+                                .map(move |milestones| RepositoryMilestones {
+                                    repository: repo,
+                                    milestones,
                                 })
                         },
-                    )))
-                    .map_err(Error::Reqwest)?;
+                    )))?;
 
                 repository_milestones.into_iter().fold(
                     HashMap::new(),
@@ -135,16 +238,14 @@ fn main() -> Result<(), Error> {
                          milestones,
                      }| {
                         for milestone in milestones {
-                            if !pattern.is_match(&milestone.title) {
-                                continue;
-                            }
-
                             match map.entry(milestone.title) {
                                 Entry::Vacant(vacant) => {
-                                    vacant.insert(vec![(milestone.url, repository.clone())]);
+                                    vacant.insert(vec![(milestone.number, repository.clone())]);
                                 }
                                 Entry::Occupied(mut occupied) => {
-                                    occupied.get_mut().push((milestone.url, repository.clone()));
+                                    occupied
+                                        .get_mut()
+                                        .push((milestone.number, repository.clone()));
                                 }
                             }
                         }
@@ -154,56 +255,105 @@ fn main() -> Result<(), Error> {
                 )
             };
 
+            let to_close: HashMap<String, Vec<(u64, String)>> = if interactive {
+                let mut titles: Vec<String> = milestones_by_title.keys().cloned().collect();
+                titles.sort();
+
+                println!();
+                println!("Type to filter, space to toggle, enter to confirm:");
+
+                let selected = FuzzyMultiSelect::new(&titles)
+                    .interact()
+                    .map_err(Error::IO)?;
+
+                selected
+                    .into_iter()
+                    .filter_map(|index| {
+                        let title = titles[index].clone();
+                        milestones_by_title
+                            .get(&title)
+                            .map(|repositories| (title, repositories.clone()))
+                    })
+                    .collect()
+            } else {
+                let pattern = pattern.expect("pattern is present whenever interactive is false");
+
+                milestones_by_title
+                    .into_iter()
+                    .filter(|(title, _)| pattern.is_match(title))
+                    .collect()
+            };
+
             println!();
-            println!(
-                "Found {} open milestones matching the pattern '{}':",
-                matching_milestones.len(),
-                pattern
-            );
+            println!("Found {} open milestone(s) to close:", to_close.len());
 
-            for (index, (milestone, repositories)) in matching_milestones.into_iter().enumerate() {
+            for (index, (milestone, repositories)) in to_close.into_iter().enumerate() {
                 println!("({}) '{}' is open in:", index + 1, milestone);
                 for (_, repository) in &repositories {
                     println!(" - {}", repository);
                 }
                 println!();
 
-                if dialoguer::Confirmation::new()
-                    .with_text(&format!(
-                        "Close milestone '{}' in those repositories?",
-                        milestone
-                    ))
-                    .interact()
-                    .map_err(Error::IO)?
-                {
-                    let username = username.clone();
-                    let token = token.clone();
+                let should_close = interactive
+                    || dialoguer::Confirmation::new()
+                        .with_text(&format!(
+                            "Close milestone '{}' in those repositories?",
+                            milestone
+                        ))
+                        .interact()
+                        .map_err(Error::IO)?;
+
+                if should_close {
                     let client = client.clone();
 
-                    runtime
-                        .block_on(future::join_all(
-                            repositories
-                                .into_iter()
-                                .map(move |(url, repo)| {
-                                    client
-                                        .clone()
-                                        .patch(&url)
-                                        .header("Accept", "application/vnd.github.v3+json")
-                                        .basic_auth(username.clone(), Some(token.clone()))
-                                        .json(&serde_json::json!({
-                                            "state": "closed"
-                                        }))
-                                        .send()
-                                        .and_then(move |response| {
-                                            if !response.status().is_success() {
-                                                eprintln!("Failed to close milestone for repository {}", repo);
-                                            }
-                                            Ok(())
-                                        })
-                                })
-                                .collect::<Vec<_>>(),
-                        ))
-                        .map_err(Error::Reqwest)?;
+                    let outcomes = runtime.block_on(future::join_all(
+                        repositories
+                            .into_iter()
+                            .map(move |(number, repo)| {
+                                let (owner, name) = split_repo(&repo);
+                                let milestones = client.repo(owner, name).milestones();
+
+                                close_with_gate(milestones, number, require_green)
+                                    .map(move |outcome| (repo, outcome))
+                            })
+                            .collect::<Vec<_>>(),
+                    ))?;
+
+                    for (repo, outcome) in outcomes {
+                        match outcome {
+                            CloseOutcome::Closed => println!(" - {}: closed", repo),
+                            CloseOutcome::SkippedPending => {
+                                println!(" - {}: skipped (CI still pending)", repo)
+                            }
+                            CloseOutcome::Blocked => {
+                                println!(" - {}: blocked (CI failing)", repo)
+                            }
+                            CloseOutcome::Failed(err) => {
+                                eprintln!(" - {}: failed ({:?})", repo, err)
+                            }
+                        }
+                    }
+                }
+            }
+        }
+        Commands::Clone { into, concurrency } => {
+            let settings::Root {
+                github: settings::GitHub { repositories, .. },
+            } = settings;
+
+            std::fs::create_dir_all(&into).map_err(Error::IO)?;
+
+            let results = clone::clone_all(repositories, &into, concurrency);
+            let failures: Vec<(String, clone::Error)> = results
+                .into_iter()
+                .filter_map(|(repo, result)| result.err().map(|err| (repo, err)))
+                .collect();
+
+            if !failures.is_empty() {
+                eprintln!();
+                eprintln!("{} repositories failed:", failures.len());
+                for (repo, err) in &failures {
+                    eprintln!(" - {}: {:?}", repo, err);
                 }
             }
         }
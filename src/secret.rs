@@ -0,0 +1,43 @@
+use std::fmt;
+
+/// A secret value that never appears in `Debug` output, regardless of how it
+/// was obtained.
+#[derive(Clone)]
+pub struct Secret(String);
+
+impl Secret {
+    pub fn expose(&self) -> &str {
+        &self.0
+    }
+
+    /// Resolves a value as configured in `settings.toml`: `env:NAME` reads the
+    /// environment variable `NAME`, `keyring:SERVICE` reads the OS credential
+    /// store entry for `SERVICE`/`account`, and anything else is taken as a
+    /// literal value, kept for backward compatibility with existing configs.
+    pub fn resolve(raw: &str, account: &str) -> Result<Secret, Error> {
+        if let Some(name) = raw.strip_prefix("env:") {
+            return std::env::var(name)
+                .map(Secret)
+                .map_err(|_| Error::EnvVarNotSet(name.to_string()));
+        }
+
+        if let Some(service) = raw.strip_prefix("keyring:") {
+            let entry = keyring::Entry::new(service, account).map_err(Error::Keyring)?;
+            return entry.get_password().map(Secret).map_err(Error::Keyring);
+        }
+
+        Ok(Secret(raw.to_string()))
+    }
+}
+
+impl fmt::Debug for Secret {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("Secret(\"***REDACTED***\")")
+    }
+}
+
+#[derive(Debug)]
+pub enum Error {
+    EnvVarNotSet(String),
+    Keyring(keyring::Error),
+}